@@ -1,10 +1,52 @@
-use lazy_static::lazy_static;
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// The public base URL of the site, used anywhere an absolute link is
+/// needed (OG `url` meta tags, the RSS feed's channel/item links).
+pub(crate) const SITE_URL: &str = "https://example.com";
+
+/// Strips tags out of already-rendered post HTML and truncates to
+/// `max_chars`, adding a trailing `…` when it cuts, for use in `<meta description>`/OG/Twitter tags -- those
+/// want plain text, not literal Markdown syntax or raw HTML. Runs on
+/// `content_html`, which is identical on the server and the hydrated
+/// client, so the result doesn't need to be ssr-gated.
+fn html_to_excerpt(html: &str, max_chars: usize) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if text.chars().count() > max_chars {
+        let truncated: String = text.chars().take(max_chars).collect();
+        format!("{truncated}…")
+    } else {
+        text
+    }
+}
+
+#[cfg(feature = "ssr")]
+fn render_markdown(content: &str) -> String {
+    use pulldown_cmark::{html, Options, Parser};
+
+    let parser = Parser::new_ext(content, Options::all());
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser);
+
+    // The blog content is untrusted input, so strip anything that could
+    // execute script in the browser before it's ever sent to the client.
+    ammonia::clean(&unsafe_html)
+}
+
 #[component]
 pub fn App() -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
@@ -20,6 +62,7 @@ pub fn App() -> impl IntoView {
             <a href="/home/in-order">"/home/in-order"</a>
             <a href="/home/async">"/home/async"</a>
             <a href="/home/partially-blocked">"/home/partially-blocked"</a>
+            <a href="/tags">"/tags"</a>
         </nav>
 
         <p>"Disable javascript to see what async renders server-side vs. others."</p>
@@ -45,6 +88,11 @@ pub fn App() -> impl IntoView {
                         view=PostPage
                         ssr=SsrMode::InOrder
                     />
+
+                    // The tag list needs to set the page <Title>, so it also
+                    // needs async rendering.
+                    <Route path="/tags" view=TagsPage ssr=SsrMode::Async/>
+                    <Route path="/tag/:name" view=TagPage ssr=SsrMode::Async/>
                 </Routes>
             </main>
         </Router>
@@ -117,13 +165,34 @@ fn Post(id: usize) -> impl IntoView {
             view! {
                 // render content
                 <h1>{&post.title}</h1>
-                <p>{&post.content}</p>
+                // the server already rendered the Markdown to sanitized HTML,
+                // so the client reuses that string instead of re-parsing it
+                // (this also keeps SSR/CSR output identical, avoiding a
+                // hydration mismatch)
+                <div class="post-content" inner_html=post.content_html.clone()></div>
+                <ul class="tags" style="display: flex; gap: 0.5em; list-style: none; padding: 0;">
+                    {post.tags.iter()
+                        .map(|tag| {
+                            let href = format!("/tag/{tag}");
+                            view! { <li><a href=href>{tag.clone()}</a></li> }
+                        })
+                        .collect_view()}
+                </ul>
 
-                // since we're using async rendering for this page,
-                // this metadata should be included in the actual HTML <head>
-                // when it's first served
-                // <Title text=post.title.clone()/>
-                // <Meta name="description" content=post.content.clone()/>
+                // Since we're using async rendering for this page (`SsrMode::Async`),
+                // the whole view -- including this metadata -- resolves before the
+                // server sends the first byte, so it's present in the initial HTML
+                // rather than arriving late via a client-side patch. Try the same
+                // thing on the out-of-order home route and compare the initial HTML:
+                // the <Suspense/> fallback ships first there, so no per-post <head>
+                // tags can be included up front.
+                <Title text=post.title.clone()/>
+                <Meta name="description" content=html_to_excerpt(&post.content_html, 160)/>
+                <Meta property="og:title" content=post.title.clone()/>
+                <Meta property="og:description" content=html_to_excerpt(&post.content_html, 160)/>
+                <Meta property="og:type" content="article"/>
+                <Meta property="og:url" content=format!("{SITE_URL}/post/{}", post.id)/>
+                <Meta name="twitter:card" content="summary"/>
             }
         })
     };
@@ -176,25 +245,83 @@ fn Comments(post_id: usize) -> impl IntoView {
     }
 }
 
-// Dummy API
-lazy_static! {
-    static ref POSTS: Vec<Post> = vec![
-        Post {
-            id: 0,
-            title: "My first post".to_string(),
-            content: "This is my first post".to_string(),
-        },
-        Post {
-            id: 1,
-            title: "My second post".to_string(),
-            content: "This is my second post".to_string(),
-        },
-        Post {
-            id: 2,
-            title: "My third post".to_string(),
-            content: "This is my third post".to_string(),
-        },
-    ];
+#[component]
+fn TagsPage() -> impl IntoView {
+    let tags = create_resource(|| (), |_| async { list_post_metadata().await });
+
+    let tags_view = move || {
+        tags.and_then(|posts| {
+            let mut counts: Vec<(String, usize)> = Vec::new();
+            for post in posts {
+                for tag in &post.tags {
+                    match counts.iter_mut().find(|(name, _)| name == tag) {
+                        Some((_, count)) => *count += 1,
+                        None => counts.push((tag.clone(), 1)),
+                    }
+                }
+            }
+            counts.sort_by(|a, b| a.0.cmp(&b.0));
+
+            counts
+                .into_iter()
+                .map(|(tag, count)| {
+                    let href = format!("/tag/{tag}");
+                    view! {
+                        <li>
+                            <a href=href>{tag}</a>
+                            " (" {count} ")"
+                        </li>
+                    }
+                })
+                .collect_view()
+        })
+    };
+
+    view! {
+        <Title text="All tags"/>
+        <h1>"Tags"</h1>
+        <Suspense fallback=move || view! { <p>"Loading tags..."</p> }>
+            <ul>{tags_view}</ul>
+        </Suspense>
+    }
+}
+
+#[derive(Params, Clone, Debug, PartialEq, Eq)]
+pub struct TagParams {
+    name: Option<String>,
+}
+
+#[component]
+fn TagPage() -> impl IntoView {
+    let query = use_params::<TagParams>();
+    let name = move || {
+        query.with(|q| {
+            q.as_ref()
+                .map(|q| q.name.clone().unwrap_or_default())
+                .unwrap_or_default()
+        })
+    };
+
+    let posts = create_resource(name, |name| async move {
+        list_post_metadata_by_tag(name).await
+    });
+
+    let posts_view = move || {
+        posts.and_then(|posts| {
+            posts
+                .iter()
+                .map(|post| view! { <Post id=post.id/> })
+                .collect_view()
+        })
+    };
+
+    view! {
+        <Title text=move || format!("Posts tagged \"{}\"", name())/>
+        <h1>"Posts tagged \"" {name} "\""</h1>
+        <Suspense fallback=move || view! { <p>"Loading posts..."</p> }>
+            {posts_view}
+        </Suspense>
+    }
 }
 
 #[derive(Error, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -209,49 +336,122 @@ pub enum PostError {
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Post {
-    id: usize,
-    title: String,
-    content: String,
+    pub(crate) id: usize,
+    pub(crate) title: String,
+    pub(crate) content: String,
+    /// The `content` Markdown, already rendered (and sanitized) to HTML on
+    /// the server. Sent down with the rest of the post so the client can
+    /// reuse it via `inner_html` rather than rendering Markdown itself.
+    pub(crate) content_html: String,
+    pub(crate) tags: Vec<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PostMetadata {
-    id: usize,
-    title: String,
+    pub(crate) id: usize,
+    pub(crate) title: String,
+    pub(crate) tags: Vec<String>,
 }
 
-#[server]
+// Post lists and post bodies are the heaviest payloads this example sends,
+// so they opt into CBOR instead of the default URL-encoded/JSON wire format
+// to shrink them. `server_fn`'s `encoding` fixes the request/response
+// format for an endpoint at codegen time, not per-request -- there's no
+// `Accept`/`Content-Type` negotiation or JSON fallback here, just a
+// statically-chosen binary encoding. `PostMetadata`/`Post` (already
+// `Serialize`/`Deserialize`) round-trip unchanged either way, as long as
+// client and server are built from the same source.
+#[server(encoding = "Cbor")]
 pub async fn list_post_metadata() -> Result<Vec<PostMetadata>, ServerFnError> {
-    eprintln!("list_post_metadata: start");
-    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-    eprintln!("list_post_metadata: end");
-    Ok(POSTS
-        .iter()
-        .map(|data| PostMetadata {
-            id: data.id,
-            title: data.title.clone(),
-        })
-        .collect())
+    crate::db::list_post_metadata()
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
 }
 
-#[server]
-pub async fn get_post(id: usize) -> Result<Option<Post>, ServerFnError> {
-    eprintln!("get_post: start");
-    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-    eprintln!("get_post: end");
-    Ok(POSTS.iter().find(|post| post.id == id).cloned())
+#[server(encoding = "Cbor")]
+pub async fn list_post_metadata_by_tag(
+    tag: String,
+) -> Result<Vec<PostMetadata>, ServerFnError> {
+    crate::db::list_post_metadata_by_tag(tag)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
 }
 
+#[server(encoding = "Cbor")]
+pub async fn get_post(id: usize) -> Result<Option<Post>, ServerFnError> {
+    let post = crate::db::get_post(id)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+    Ok(post.map(|mut post| {
+        // Render Markdown -> sanitized HTML on the server, once, so the
+        // hydrated client can reuse `content_html` verbatim instead of
+        // re-parsing `content` (which would have to happen deterministically
+        // on both sides to avoid a hydration mismatch).
+        post.content_html = render_markdown(&post.content);
+        post
+    }))
+}
 
-
-async fn get_comments(post_id: usize) -> Result<Vec<Comment>, ServerFnError> {
-    eprintln!("get_comments: start");
-    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-    eprintln!("get_comments: end");
-    Ok(vec![])
+#[server]
+pub async fn get_comments(post_id: usize) -> Result<Vec<Comment>, ServerFnError> {
+    crate::db::get_comments(post_id)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-struct Comment {
-    // unused
+pub struct Comment {
+    pub(crate) id: i64,
+    pub(crate) post_id: i64,
+    pub(crate) body: String,
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use super::*;
+
+    // The `Post` route's SEO metadata only ever makes it into the initial
+    // HTML because `/post/:id` uses `SsrMode::Async`: the whole view,
+    // including `<Title>`/`<Meta>`, resolves before the first byte is sent.
+    // `HomePage`'s out-of-order route ships its `<Suspense/>` fallback
+    // first, so there's nothing to inject into `<head>` up front -- these
+    // two assertions are what demonstrate that distinction.
+
+    /// `get_post`/`list_post_metadata` pull their `SqlitePool` out of
+    /// context, so rendering either `Post` or `HomePage` needs one in scope
+    /// -- an in-memory database, migrated the same way the real app is.
+    async fn test_pool() -> sqlx::SqlitePool {
+        crate::db::init_pool("sqlite::memory:")
+            .await
+            .expect("failed to set up in-memory test database")
+    }
+
+    #[tokio::test]
+    async fn post_page_includes_seo_meta_tags_in_ssr_output() {
+        provide_meta_context();
+        provide_context(test_pool().await);
+        let html = leptos::ssr::render_to_string(move || view! { <Post id=0/> }).await;
+
+        assert!(html.contains("og:title"), "missing og:title in: {html}");
+        assert!(
+            html.contains("og:description"),
+            "missing og:description in: {html}"
+        );
+        assert!(
+            html.contains("twitter:card"),
+            "missing twitter:card in: {html}"
+        );
+    }
+
+    #[tokio::test]
+    async fn home_page_omits_per_post_seo_meta_tags_in_ssr_output() {
+        provide_meta_context();
+        provide_context(test_pool().await);
+        let html = leptos::ssr::render_to_string(move || view! { <HomePage/> }).await;
+
+        assert!(
+            !html.contains("og:title"),
+            "out-of-order home route shouldn't carry per-post OG metadata: {html}"
+        );
+    }
 }
\ No newline at end of file
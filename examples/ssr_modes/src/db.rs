@@ -0,0 +1,112 @@
+//! The `ssr`-only data layer backing the blog. Replaces the old in-memory
+//! `POSTS` dummy API with a real SQLite database (Postgres would work
+//! identically via `sqlx::Pool<Postgres>`), following the same
+//! `expect_context`-a-pool pattern as the other sqlx-based Leptos examples.
+#![cfg(feature = "ssr")]
+
+use crate::app::{Comment, Post, PostMetadata};
+use leptos::expect_context;
+use sqlx::SqlitePool;
+
+/// Opens the pool and runs any pending migrations. Call this once at
+/// startup and hand the result to `provide_context` in the Axum
+/// integration's context-provider hook, so every server fn can pull it
+/// back out with `expect_context`.
+pub async fn init_pool(database_url: &str) -> Result<SqlitePool, sqlx::Error> {
+    let pool = SqlitePool::connect(database_url).await?;
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    Ok(pool)
+}
+
+fn pool() -> SqlitePool {
+    expect_context::<SqlitePool>()
+}
+
+pub async fn list_post_metadata() -> Result<Vec<PostMetadata>, sqlx::Error> {
+    let pool = pool();
+    let posts = sqlx::query!("select id, title from posts order by id")
+        .fetch_all(&pool)
+        .await?;
+
+    let mut metadata = Vec::with_capacity(posts.len());
+    for post in posts {
+        let tags = tags_for_post(&pool, post.id).await?;
+        metadata.push(PostMetadata {
+            id: post.id as usize,
+            title: post.title,
+            tags,
+        });
+    }
+    Ok(metadata)
+}
+
+pub async fn list_post_metadata_by_tag(
+    tag: String,
+) -> Result<Vec<PostMetadata>, sqlx::Error> {
+    let pool = pool();
+    let posts = sqlx::query!(
+        "select posts.id, posts.title \
+         from posts \
+         join post_tags on post_tags.post_id = posts.id \
+         where post_tags.tag = $1 \
+         order by posts.id",
+        tag
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let mut metadata = Vec::with_capacity(posts.len());
+    for post in posts {
+        let tags = tags_for_post(&pool, post.id).await?;
+        metadata.push(PostMetadata {
+            id: post.id as usize,
+            title: post.title,
+            tags,
+        });
+    }
+    Ok(metadata)
+}
+
+pub async fn get_post(id: usize) -> Result<Option<Post>, sqlx::Error> {
+    let pool = pool();
+    let id = id as i64;
+    let row = sqlx::query!(
+        "select id, title, content from posts where id = $1",
+        id
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let tags = tags_for_post(&pool, id).await?;
+    Ok(Some(Post {
+        id: row.id as usize,
+        title: row.title,
+        content: row.content,
+        content_html: String::new(),
+        tags,
+    }))
+}
+
+pub async fn get_comments(post_id: usize) -> Result<Vec<Comment>, sqlx::Error> {
+    let pool = pool();
+    sqlx::query_as!(
+        Comment,
+        "select id, post_id, body from comments where post_id = $1 order by id",
+        post_id as i64
+    )
+    .fetch_all(&pool)
+    .await
+}
+
+async fn tags_for_post(pool: &SqlitePool, post_id: i64) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query!(
+        "select tag from post_tags where post_id = $1 order by tag",
+        post_id
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|row| row.tag).collect())
+}
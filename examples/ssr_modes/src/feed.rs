@@ -0,0 +1,69 @@
+//! An RSS feed for the blog, built from the same data as the rest of the
+//! app. Unlike the routes in [`crate::app`], a feed isn't a view, so it's a
+//! plain async fn rather than a `#[component]`, returned as a `Content-Type:
+//! application/rss+xml` response. Depends on `axum`/`rss`, so (like
+//! `crate::db`) this module only exists in the `ssr` build.
+//!
+//! Wire it into the Axum router alongside the Leptos routes, e.g.
+//!
+//! ```ignore
+//! Router::new()
+//!     .route("/feed.xml", get(feed::rss_feed))
+//!     .leptos_routes(&leptos_options, routes, App)
+//! ```
+#![cfg(feature = "ssr")]
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use rss::{ChannelBuilder, Guid, ItemBuilder};
+
+use crate::app::{get_post, list_post_metadata, SITE_URL};
+
+/// Serves `/feed.xml`: an RSS 2.0 channel with one `<item>` per post,
+/// reusing the same `list_post_metadata`/`get_post` server fns the rest of
+/// the app uses, so the feed can never drift out of sync with the site.
+pub async fn rss_feed() -> Response {
+    match build_feed().await {
+        Ok(xml) => (
+            [(axum::http::header::CONTENT_TYPE, "application/rss+xml")],
+            xml,
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("rss_feed: failed to build feed: {e}");
+            (StatusCode::INTERNAL_SERVER_ERROR, "failed to build feed").into_response()
+        }
+    }
+}
+
+async fn build_feed() -> Result<String, leptos::ServerFnError> {
+    let metadata = list_post_metadata().await?;
+
+    let mut items = Vec::with_capacity(metadata.len());
+    for post in metadata {
+        let Some(post) = get_post(post.id).await? else {
+            continue;
+        };
+        let link = format!("{SITE_URL}/post/{}", post.id);
+        items.push(
+            ItemBuilder::default()
+                .title(Some(post.title))
+                .link(Some(link.clone()))
+                .guid(Some(Guid {
+                    value: link,
+                    permalink: true,
+                }))
+                .description(Some(post.content_html))
+                .build(),
+        );
+    }
+
+    let channel = ChannelBuilder::default()
+        .title("My Great Blog")
+        .link(SITE_URL)
+        .description("Musings from the ssr_modes example blog.")
+        .items(items)
+        .build();
+
+    Ok(channel.to_string())
+}